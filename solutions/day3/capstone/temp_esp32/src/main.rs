@@ -4,15 +4,30 @@
 //! It can run in two modes:
 //! - Simulation mode: Runs on desktop for testing
 //! - Hardware mode: Runs on actual ESP32-C3
+//!
+//! Both modes accept JSON `EmbeddedCommand`s, one per line, and answer each with a
+//! `RESPONSE_JSON:` line. In simulation mode, pass `--stdin` to script the device
+//! from the desktop; commands are served between samples while the monitoring loop
+//! runs, then until stdin is closed:
+//!
+//! ```text
+//! echo '"GetStatus"' | cargo run --no-default-features --features simulation -- --stdin
+//! ```
 
 #![cfg_attr(not(feature = "simulation"), no_std)]
 #![cfg_attr(feature = "hardware", no_main)]
 
 use temp_embedded::{
     EmbeddedTemperatureStore, EmbeddedProtocolHandler, EmbeddedCommand, EmbeddedResponse,
-    EmbeddedTemperatureReading, Temperature, READING_BUFFER_SIZE
+    EmbeddedTemperatureReading, EmbeddedError, Temperature, READING_BUFFER_SIZE
 };
 
+/// Maximum length of a single JSON command line (excluding the newline)
+const COMMAND_LINE_CAPACITY: usize = 128;
+
+/// Maximum length of a serialized JSON response
+const RESPONSE_JSON_CAPACITY: usize = 512;
+
 #[cfg(feature = "simulation")]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🌡️ ESP32-C3 Temperature Monitor (Simulation Mode)");
@@ -26,18 +41,34 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     protocol_handler.init(get_boot_timestamp());
 
+    // Scriptable mode: commands from stdin are served between samples, like on hardware
+    let commands = std::env::args()
+        .any(|arg| arg == "--stdin")
+        .then(|| spawn_command_reader(std::io::stdin()));
+
     println!("✅ System initialized");
     println!("📊 Buffer capacity: {} readings", store.capacity());
     println!("⚡ Sample rate: {} Hz", temp_embedded::SAMPLE_RATE_HZ);
     println!("💾 Memory usage: ~{} bytes",
              std::mem::size_of_val(&store) + std::mem::size_of_val(&protocol_handler));
 
-    // Demonstrate serde JSON functionality
-    println!("\n=== SERDE DEMO: JSON Serialization/Deserialization ===");
-    demonstrate_serde_functionality(&mut protocol_handler)?;
+    // Demonstrate serde JSON functionality (skipped when scripted, so answers only
+    // reflect readings taken by the monitoring loop)
+    if commands.is_none() {
+        println!("\n=== SERDE DEMO: JSON Serialization/Deserialization ===");
+        demonstrate_serde_functionality(&mut protocol_handler)?;
+    }
 
     // Simulate temperature monitoring loop
-    simulate_monitoring_loop(&mut protocol_handler)?;
+    simulate_monitoring_loop(&mut protocol_handler, commands.as_ref())?;
+
+    // Keep answering scripted commands until stdin is closed
+    if let Some(commands) = &commands {
+        for result in commands {
+            let response = dispatch_command(&mut protocol_handler, result, get_current_timestamp());
+            println!("RESPONSE_JSON: {}", response_to_json(&response));
+        }
+    }
 
     println!("\n🎉 ESP32-C3 simulation completed successfully!");
     Ok(())
@@ -86,11 +117,85 @@ fn demonstrate_serde_functionality(
     Ok(())
 }
 
+/// Read commands from `input` on a background thread, one JSON command per line
+#[cfg(feature = "simulation")]
+fn spawn_command_reader<R>(input: R) -> std::sync::mpsc::Receiver<CommandResult>
+where
+    R: std::io::Read + Send + 'static,
+{
+    use std::io::{BufReader, Read};
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+
+    std::thread::spawn(move || {
+        let mut reader: CommandReader<COMMAND_LINE_CAPACITY> = CommandReader::new();
+
+        for byte in BufReader::new(input).bytes() {
+            let Ok(byte) = byte else { break };
+            if let Some(result) = reader.push_byte(byte) {
+                if sender.send(result).is_err() {
+                    return;
+                }
+            }
+        }
+
+        // Treat EOF as the end of a final, unterminated line
+        if let Some(result) = reader.push_byte(b'\n') {
+            let _ = sender.send(result);
+        }
+    });
+
+    receiver
+}
+
+/// Wait for one sample period, answering any commands that arrive meanwhile
+#[cfg(feature = "simulation")]
+fn serve_commands_for(
+    protocol_handler: &mut EmbeddedProtocolHandler<READING_BUFFER_SIZE>,
+    commands: Option<&std::sync::mpsc::Receiver<CommandResult>>,
+    period: std::time::Duration
+) {
+    use std::sync::mpsc::RecvTimeoutError;
+    use std::time::Instant;
+
+    let deadline = Instant::now() + period;
+
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        let result = match commands.map(|commands| commands.recv_timeout(remaining)) {
+            Some(Ok(result)) => result,
+            Some(Err(RecvTimeoutError::Timeout)) => break,
+            // No command source, or stdin was closed: just sleep out the period
+            None | Some(Err(RecvTimeoutError::Disconnected)) => {
+                std::thread::sleep(remaining);
+                break;
+            }
+        };
+
+        let response = dispatch_command(protocol_handler, result, get_current_timestamp());
+        println!("RESPONSE_JSON: {}", response_to_json(&response));
+    }
+}
+
+/// Take one simulated sensor sample and store it, returning its timestamp
+#[cfg(feature = "simulation")]
+fn take_simulated_reading(
+    protocol_handler: &mut EmbeddedProtocolHandler<READING_BUFFER_SIZE>,
+    reading_count: u32
+) -> Result<u32, &'static str> {
+    // Simulate ADC reading from temperature sensor
+    let adc_value = simulate_adc_reading(reading_count);
+    let temperature = Temperature::from_embedded_sensor(adc_value);
+    let timestamp = get_current_timestamp();
+
+    protocol_handler.add_reading(temperature, timestamp)?;
+    Ok(timestamp)
+}
+
 #[cfg(feature = "simulation")]
 fn simulate_monitoring_loop(
-    protocol_handler: &mut EmbeddedProtocolHandler<READING_BUFFER_SIZE>
+    protocol_handler: &mut EmbeddedProtocolHandler<READING_BUFFER_SIZE>,
+    commands: Option<&std::sync::mpsc::Receiver<CommandResult>>
 ) -> Result<(), Box<dyn std::error::Error>> {
-    use std::thread::sleep;
     use std::time::Duration;
 
     println!("\n🔄 Starting monitoring loop...");
@@ -99,24 +204,20 @@ fn simulate_monitoring_loop(
 
     // Simulate 50 readings (5 seconds at 10Hz)
     for cycle in 0..50 {
-        // Simulate ADC reading from temperature sensor
-        let adc_value = simulate_adc_reading(reading_count);
-        let temperature = Temperature::from_embedded_sensor(adc_value);
-        let timestamp = get_current_timestamp();
-
         // Add reading to the system
-        if let Err(e) = protocol_handler.add_reading(temperature, timestamp) {
-            eprintln!("Storage error: {}", e);
-        } else {
-            reading_count += 1;
+        match take_simulated_reading(protocol_handler, reading_count) {
+            Err(e) => eprintln!("Storage error: {}", e),
+            Ok(timestamp) => {
+                reading_count += 1;
 
-            if cycle % 10 == 0 {
-                print_status_update(protocol_handler, timestamp, cycle);
+                if cycle % 10 == 0 {
+                    print_status_update(protocol_handler, timestamp, cycle);
+                }
             }
         }
 
-        // Wait 100ms to simulate 10Hz sampling
-        sleep(Duration::from_millis(100));
+        // Wait 100ms to simulate 10Hz sampling, serving commands meanwhile
+        serve_commands_for(protocol_handler, commands, Duration::from_millis(100));
     }
 
     // Final status report
@@ -140,6 +241,11 @@ fn print_status_update(
         timestamp
     );
 
+    // Same machine-readable lines as the hardware build, for temp_gateway
+    if let Ok(json) = serde_json::to_string(&status_response) {
+        println!("STATUS_JSON: {}", json);
+    }
+
     if let EmbeddedResponse::Status {
         uptime_seconds,
         reading_count,
@@ -152,6 +258,15 @@ fn print_status_update(
         println!("  💾 Buffer Usage: {}%", buffer_usage);
     }
 
+    let stats_response = protocol_handler.process_command(
+        EmbeddedCommand::GetStats,
+        timestamp
+    );
+
+    if let Ok(json) = serde_json::to_string(&stats_response) {
+        println!("STATS_JSON: {}", json);
+    }
+
     // Get latest reading
     let reading_response = protocol_handler.process_command(
         EmbeddedCommand::GetLatestReading,
@@ -161,6 +276,10 @@ fn print_status_update(
     if let EmbeddedResponse::Reading(reading) = reading_response {
         println!("  🌡️  Latest: {:.1}°C @ {}s",
                  reading.temperature.celsius, reading.timestamp);
+
+        if let Ok(json) = serde_json::to_string(&reading) {
+            println!("READING_JSON: {}", json);
+        }
     }
 }
 
@@ -208,6 +327,8 @@ fn print_final_statistics(
 use esp_hal::{
     clock::CpuClock,
     time::{Duration, Instant},
+    usb_serial_jtag::UsbSerialJtag,
+    Blocking,
 };
 
 #[cfg(feature = "hardware")]
@@ -240,12 +361,12 @@ fn main() -> ! {
     esp_println!("🌡️ ESP32-C3 Temperature Monitor Starting");
     esp_println!("📊 Buffer capacity: {} readings", READING_BUFFER_SIZE);
     esp_println!("⚡ Sample rate: {} Hz", temp_embedded::SAMPLE_RATE_HZ);
-    esp_println!("📋 JSON output format: STATUS_JSON, STATS_JSON, READING_JSON");
-    esp_println!("🔧 Send JSON commands: {{\"GetStatus\"}}, {{\"GetStats\"}}, {{\"GetLatestReading\"}}");
-    esp_println!("=== SERDE DEMO: Processing sample commands ===");
+    esp_println!("📋 JSON output format: STATUS_JSON, STATS_JSON, READING_JSON, RESPONSE_JSON");
+    esp_println!("🔧 Send JSON commands, one per line: \"GetStatus\", \"GetStats\", \"GetLatestReading\"");
 
-    // Demonstrate serde JSON command parsing and response serialization
-    demonstrate_json_commands(&mut protocol_handler, 0);
+    // Commands arrive over the same USB serial port esp-println writes to
+    let mut serial = UsbSerialJtag::new(peripherals.USB_DEVICE);
+    let mut command_reader: CommandReader<COMMAND_LINE_CAPACITY> = CommandReader::new();
 
     esp_println!("=== Starting continuous monitoring ===");
 
@@ -294,61 +415,120 @@ fn main() -> ! {
             }
         }
 
-        // Wait according to sample rate (100ms for 10Hz)
+        // Wait according to sample rate (100ms for 10Hz), serving commands meanwhile
         let delay_start = Instant::now();
-        while delay_start.elapsed() < Duration::from_millis(100) {}
+        while delay_start.elapsed() < Duration::from_millis(100) {
+            poll_serial_commands(&mut serial, &mut command_reader, &mut protocol_handler, timestamp);
+        }
     }
 }
 
 #[cfg(feature = "hardware")]
-fn demonstrate_json_commands(protocol_handler: &mut EmbeddedProtocolHandler<READING_BUFFER_SIZE>, timestamp: u32) {
-    esp_println!("📝 Demonstrating JSON command processing with serde:");
-
-    // Add some sample readings first
-    let temp1 = Temperature::from_embedded_sensor(simulate_adc_reading_hardware(0));
-    let temp2 = Temperature::from_embedded_sensor(simulate_adc_reading_hardware(10));
-    let temp3 = Temperature::from_embedded_sensor(simulate_adc_reading_hardware(20));
-
-    let _ = protocol_handler.add_reading(temp1, timestamp);
-    let _ = protocol_handler.add_reading(temp2, timestamp + 1);
-    let _ = protocol_handler.add_reading(temp3, timestamp + 2);
-
-    // Demonstrate various commands as JSON strings (as if received from Serial Terminal)
-    let json_commands = [
-        "GetStatus",
-        "GetStats",
-        "GetLatestReading",
-        "GetReadingCount"
-    ];
+fn poll_serial_commands(
+    serial: &mut UsbSerialJtag<'_, Blocking>,
+    reader: &mut CommandReader<COMMAND_LINE_CAPACITY>,
+    protocol_handler: &mut EmbeddedProtocolHandler<READING_BUFFER_SIZE>,
+    timestamp: u32
+) {
+    // Drain whatever is in the RX FIFO without blocking
+    while let Ok(byte) = serial.read_byte() {
+        if let Some(result) = reader.push_byte(byte) {
+            let response = dispatch_command(protocol_handler, result, timestamp);
+            esp_println!("RESPONSE_JSON: {}", response_to_json(&response));
+        }
+    }
+}
 
-    for cmd_name in &json_commands {
-        esp_println!("🔄 Processing command: {}", cmd_name);
+/// Errors produced while reading commands from the serial line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommandReadError {
+    /// The line did not fit into the reader's buffer and was discarded
+    LineTooLong,
+    /// The line was not a valid JSON `EmbeddedCommand`
+    InvalidJson,
+}
 
-        // Parse the command (in real implementation this would come from serial input)
-        let command = match *cmd_name {
-            "GetStatus" => EmbeddedCommand::GetStatus,
-            "GetStats" => EmbeddedCommand::GetStats,
-            "GetLatestReading" => EmbeddedCommand::GetLatestReading,
-            "GetReadingCount" => EmbeddedCommand::GetReadingCount,
-            _ => continue,
-        };
+/// Result of reading one command line
+pub type CommandResult = Result<EmbeddedCommand, CommandReadError>;
 
-        // Process the command
-        let response = protocol_handler.process_command(command, timestamp + 10);
+/// Line-oriented reader turning a serial byte stream into `EmbeddedCommand`s
+///
+/// Each command is a single line of JSON naming the variant, e.g. `"GetStatus"` or `"GetStats"`.
+/// Carriage returns are ignored and blank lines are skipped.
+pub struct CommandReader<const N: usize> {
+    line: heapless::Vec<u8, N>,
+    overflowed: bool,
+}
 
-        // Serialize response to JSON using serde
-        match serde_json_core::to_string::<_, 512>(&response) {
-            Ok(json_response) => {
-                esp_println!("✅ JSON Response: {}", json_response);
-            }
-            Err(_) => {
-                esp_println!("❌ Failed to serialize response");
+impl<const N: usize> CommandReader<N> {
+    pub const fn new() -> Self {
+        Self {
+            line: heapless::Vec::new(),
+            overflowed: false,
+        }
+    }
+
+    /// Feed one byte; returns the parsed command once a full line has been received
+    pub fn push_byte(&mut self, byte: u8) -> Option<CommandResult> {
+        match byte {
+            b'\n' => self.finish_line(),
+            b'\r' => None,
+            _ => {
+                if !self.overflowed && self.line.push(byte).is_err() {
+                    // Keep discarding until the newline so we resynchronize on the next line
+                    self.overflowed = true;
+                }
+                None
             }
         }
-        esp_println!("");
+    }
+
+    fn finish_line(&mut self) -> Option<CommandResult> {
+        let result = if self.overflowed {
+            Some(Err(CommandReadError::LineTooLong))
+        } else if self.line.iter().all(|byte| byte.is_ascii_whitespace()) {
+            None
+        } else {
+            Some(
+                serde_json_core::from_slice::<EmbeddedCommand>(&self.line)
+                    .map(|(command, _)| command)
+                    .map_err(|_| CommandReadError::InvalidJson)
+            )
+        };
+
+        self.line.clear();
+        self.overflowed = false;
+        result
+    }
+}
+
+impl<const N: usize> Default for CommandReader<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Process a received command line, mapping read errors to an error response
+fn dispatch_command<const N: usize>(
+    protocol_handler: &mut EmbeddedProtocolHandler<N>,
+    result: CommandResult,
+    timestamp: u32
+) -> EmbeddedResponse {
+    match result {
+        Ok(command) => protocol_handler.process_command(command, timestamp),
+        Err(_) => EmbeddedResponse::Error(EmbeddedError::InvalidCommand.error_code()),
     }
 }
 
+/// Serialize a response for the `RESPONSE_JSON:` line
+fn response_to_json(response: &EmbeddedResponse) -> heapless::String<RESPONSE_JSON_CAPACITY> {
+    serde_json_core::to_string(response).unwrap_or_else(|_| {
+        // An error code always fits, so the host still gets a well-formed reply
+        let fallback = EmbeddedResponse::Error(EmbeddedError::SerializationError.error_code());
+        serde_json_core::to_string(&fallback).unwrap_or_default()
+    })
+}
+
 // Utility functions that work in both simulation and hardware modes
 
 fn simulate_adc_reading(count: u32) -> u16 {
@@ -524,4 +704,79 @@ mod tests {
             assert!(temp.celsius >= 18.0 && temp.celsius <= 32.0);
         }
     }
-}
\ No newline at end of file
+
+    fn read_line(reader: &mut CommandReader<COMMAND_LINE_CAPACITY>, line: &[u8])
+        -> Option<CommandResult>
+    {
+        line.iter().find_map(|&byte| reader.push_byte(byte))
+    }
+
+    #[test]
+    fn test_command_reader_parses_json_lines() {
+        let mut reader: CommandReader<COMMAND_LINE_CAPACITY> = CommandReader::new();
+
+        let command = read_line(&mut reader, b"\"GetStatus\"\r\n");
+        assert!(matches!(command, Some(Ok(EmbeddedCommand::GetStatus))));
+
+        let command = read_line(&mut reader, b"  \"GetStats\" \n");
+        assert!(matches!(command, Some(Ok(EmbeddedCommand::GetStats))));
+
+        // Blank lines are skipped
+        assert!(read_line(&mut reader, b"\r\n").is_none());
+    }
+
+    #[test]
+    fn test_command_reader_recovers_from_bad_input() {
+        let mut reader: CommandReader<32> = CommandReader::new();
+
+        let result = b"{not json\n".iter().find_map(|&byte| reader.push_byte(byte));
+        assert_eq!(result.map(|r| r.err()), Some(Some(CommandReadError::InvalidJson)));
+
+        let result = [b'x'; 40].iter().chain(b"\n").find_map(|&byte| reader.push_byte(byte));
+        assert_eq!(result.map(|r| r.err()), Some(Some(CommandReadError::LineTooLong)));
+
+        // The next line parses normally again
+        let result = b"\"GetReadingCount\"\n".iter().find_map(|&byte| reader.push_byte(byte));
+        assert!(matches!(result, Some(Ok(EmbeddedCommand::GetReadingCount))));
+    }
+
+    #[test]
+    fn test_dispatch_command_replies_with_json() {
+        let mut handler: EmbeddedProtocolHandler<READING_BUFFER_SIZE> = EmbeddedProtocolHandler::new();
+        handler.init(0);
+        handler.add_reading(Temperature::new(22.5), 1).unwrap();
+
+        let response = dispatch_command(&mut handler, Ok(EmbeddedCommand::GetLatestReading), 2);
+        assert!(matches!(response, EmbeddedResponse::Reading(_)));
+        assert!(response_to_json(&response).starts_with("{\"Reading\""));
+
+        let response = dispatch_command(&mut handler, Err(CommandReadError::InvalidJson), 2);
+        let expected = EmbeddedError::InvalidCommand.error_code();
+        assert!(matches!(response, EmbeddedResponse::Error(code) if code == expected));
+    }
+
+    #[test]
+    fn test_stdin_commands_see_monitoring_loop_readings() {
+        let mut handler: EmbeddedProtocolHandler<READING_BUFFER_SIZE> = EmbeddedProtocolHandler::new();
+        handler.init(get_boot_timestamp());
+
+        for count in 0..5 {
+            take_simulated_reading(&mut handler, count).unwrap();
+        }
+
+        // The last command has no trailing newline, as with `echo -n`
+        let input = std::io::Cursor::new(b"\"GetReadingCount\"\n\"GetStatus\"".to_vec());
+        let responses: Vec<EmbeddedResponse> = spawn_command_reader(input)
+            .iter()
+            .map(|result| dispatch_command(&mut handler, result, get_current_timestamp()))
+            .collect();
+
+        assert!(matches!(
+            responses[..],
+            [
+                EmbeddedResponse::ReadingCount(5),
+                EmbeddedResponse::Status { reading_count: 5, .. },
+            ]
+        ));
+    }
+}
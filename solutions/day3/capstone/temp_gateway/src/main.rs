@@ -0,0 +1,653 @@
+//! Temperature Gateway
+//!
+//! Host-side bridge between a temp_esp32 device and the desktop `TemperatureStore`.
+//! It reads the device's serial output line by line, picks out the
+//! `STATUS_JSON:`, `STATS_JSON:`, `READING_JSON:` and `RESPONSE_JSON:` lines and
+//! ignores everything else (boot banners, log output, line noise).
+//!
+//! Usage:
+//!
+//! ```text
+//! temp_gateway [--follow] [PATH]
+//! ```
+//!
+//! Without `PATH` (or with `-`) the gateway reads stdin, so the simulation build
+//! can be piped straight into it:
+//!
+//! ```text
+//! (cd temp_esp32 && cargo run --no-default-features --features simulation) | cargo run -p temp_gateway
+//! ```
+//!
+//! `PATH` can be a log file, a serial device or a pty. With `--follow` the gateway
+//! keeps waiting for new output, like `tail -f`, and prints each reading and
+//! status update as it arrives. A device or pty that fails is reopened, so an
+//! unplugged device reconnects. `--follow` needs a `PATH`; stdin is always read
+//! until it is closed.
+
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::thread::sleep;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use temp_embedded::{EmbeddedResponse, EmbeddedTemperatureReading};
+use temp_store::{TemperatureReading, TemperatureStore};
+
+/// Number of readings kept by the gateway's store
+const STORE_CAPACITY: usize = 1000;
+
+/// Delay before reopening a failed device in `--follow` mode
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// How often a followed source is checked for new output
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Longest line kept while waiting for its newline; longer lines are dropped
+const MAX_LINE_LENGTH: usize = 4096;
+
+/// Device timestamps from this value on are already Unix seconds
+///
+/// The simulation build stamps readings with wall-clock time, while the
+/// hardware build counts from boot.
+const UNIX_TIMESTAMP_THRESHOLD: u32 = 1_000_000_000;
+
+/// A recognized line of device output
+#[derive(Debug, Clone)]
+pub enum DeviceLine {
+    Status(EmbeddedResponse),
+    Stats(EmbeddedResponse),
+    Reading(EmbeddedTemperatureReading),
+    Response(EmbeddedResponse),
+}
+
+/// Parse one line of device output, returning `None` for anything unrecognized
+///
+/// The prefix may appear anywhere in the line, so output mixed with log
+/// timestamps or a partially received previous line is still accepted.
+/// `STATUS_JSON:` and `STATS_JSON:` lines must carry the matching response.
+pub fn parse_line(line: &str) -> Option<DeviceLine> {
+    if let Some(json) = json_after(line, "STATUS_JSON:") {
+        return serde_json::from_str(json)
+            .ok()
+            .filter(|response| matches!(response, EmbeddedResponse::Status { .. }))
+            .map(DeviceLine::Status);
+    }
+    if let Some(json) = json_after(line, "STATS_JSON:") {
+        return serde_json::from_str(json)
+            .ok()
+            .filter(|response| matches!(response, EmbeddedResponse::Stats(_)))
+            .map(DeviceLine::Stats);
+    }
+    if let Some(json) = json_after(line, "READING_JSON:") {
+        return serde_json::from_str(json).ok().map(DeviceLine::Reading);
+    }
+    if let Some(json) = json_after(line, "RESPONSE_JSON:") {
+        return serde_json::from_str(json).ok().map(DeviceLine::Response);
+    }
+    None
+}
+
+fn json_after<'a>(line: &'a str, prefix: &str) -> Option<&'a str> {
+    line.find(prefix).map(|start| line[start + prefix.len()..].trim())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// A line whose newline has not arrived yet
+///
+/// Lines longer than `MAX_LINE_LENGTH` (line noise, a baud rate mismatch) are
+/// discarded up to the next newline so the buffer stays bounded.
+#[derive(Debug, Default)]
+pub struct PartialLine {
+    bytes: Vec<u8>,
+    overflowed: bool,
+}
+
+impl PartialLine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty() && !self.overflowed
+    }
+
+    fn push(&mut self, chunk: &[u8]) {
+        if self.overflowed {
+            return;
+        }
+        if self.bytes.len() + chunk.len() > MAX_LINE_LENGTH {
+            // Keep discarding until the newline so we resynchronize on the next line
+            self.bytes = Vec::new();
+            self.overflowed = true;
+        } else {
+            self.bytes.extend_from_slice(chunk);
+        }
+    }
+}
+
+/// Counters describing what the gateway has seen so far
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct GatewayStats {
+    pub lines: u64,
+    pub readings: u64,
+    pub duplicates: u64,
+    pub status_updates: u64,
+    pub ignored_lines: u64,
+    pub device_reboots: u64,
+    pub reconnects: u64,
+}
+
+/// Feeds parsed device output into a `TemperatureStore`
+pub struct Gateway {
+    store: TemperatureStore,
+    capacity: usize,
+    last_status: Option<EmbeddedResponse>,
+    last_stats: Option<EmbeddedResponse>,
+    /// Host time (Unix seconds) at which the device booted
+    device_boot_time: Option<u64>,
+    last_uptime: Option<u32>,
+    /// Device timestamp and temperature bits of readings stored since the device last booted
+    seen_readings: BTreeSet<(u32, u32)>,
+    echo: bool,
+    stats: GatewayStats,
+}
+
+impl Gateway {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            store: TemperatureStore::new(capacity),
+            capacity,
+            last_status: None,
+            last_stats: None,
+            device_boot_time: None,
+            last_uptime: None,
+            seen_readings: BTreeSet::new(),
+            echo: false,
+            stats: GatewayStats::default(),
+        }
+    }
+
+    /// Print each stored reading and status update as it arrives
+    pub fn set_echo(&mut self, echo: bool) {
+        self.echo = echo;
+    }
+
+    pub fn store(&self) -> &TemperatureStore {
+        &self.store
+    }
+
+    pub fn stats(&self) -> GatewayStats {
+        self.stats
+    }
+
+    pub fn last_status(&self) -> Option<&EmbeddedResponse> {
+        self.last_status.as_ref()
+    }
+
+    pub fn last_stats(&self) -> Option<&EmbeddedResponse> {
+        self.last_stats.as_ref()
+    }
+
+    /// Process a single line of device output received now
+    pub fn ingest_line(&mut self, line: &str) {
+        self.ingest_line_at(line, unix_now());
+    }
+
+    /// Process a single line of device output received at host time `now`
+    pub fn ingest_line_at(&mut self, line: &str, now: u64) {
+        self.stats.lines += 1;
+
+        match parse_line(line) {
+            Some(DeviceLine::Reading(reading)) => self.store_reading(reading, now, false),
+            Some(DeviceLine::Status(response)) | Some(DeviceLine::Stats(response))
+            | Some(DeviceLine::Response(response)) => self.handle_response(response, now),
+            None => self.stats.ignored_lines += 1,
+        }
+    }
+
+    /// Process every line from `reader` until it ends or fails
+    pub fn ingest_reader<R: BufRead>(&mut self, mut reader: R) -> io::Result<()> {
+        let mut partial = PartialLine::new();
+        self.ingest_available(&mut reader, &mut partial)?;
+
+        // The source has ended, so a trailing line without newline is complete
+        if !partial.is_empty() {
+            self.finish_line(&mut partial);
+        }
+        Ok(())
+    }
+
+    /// Process every complete line `reader` has available right now
+    ///
+    /// A trailing line without newline stays in `partial` until the rest of
+    /// it arrives, so a file that is still being written can be tailed.
+    pub fn ingest_available<R: BufRead>(&mut self, reader: &mut R, partial: &mut PartialLine) -> io::Result<()> {
+        loop {
+            let available = reader.fill_buf()?;
+            if available.is_empty() {
+                return Ok(());
+            }
+
+            let newline = available.iter().position(|&byte| byte == b'\n');
+            let consumed = newline.map_or(available.len(), |end| end + 1);
+            partial.push(&available[..consumed]);
+            reader.consume(consumed);
+
+            if newline.is_some() {
+                self.finish_line(partial);
+            }
+        }
+    }
+
+    fn finish_line(&mut self, partial: &mut PartialLine) {
+        if partial.overflowed {
+            self.stats.lines += 1;
+            self.stats.ignored_lines += 1;
+        } else {
+            // Serial links deliver the occasional invalid byte; don't let it end the session
+            self.ingest_line(&String::from_utf8_lossy(&partial.bytes));
+        }
+        *partial = PartialLine::new();
+    }
+
+    fn handle_response(&mut self, response: EmbeddedResponse, now: u64) {
+        match response {
+            EmbeddedResponse::Reading(reading) => self.store_reading(reading, now, true),
+            EmbeddedResponse::Status { uptime_seconds, reading_count, buffer_usage, .. } => {
+                self.track_uptime(uptime_seconds, now);
+                self.stats.status_updates += 1;
+                if self.echo {
+                    println!("📊 Device up {}s, {} readings, {}% buffer",
+                             uptime_seconds, reading_count, buffer_usage);
+                }
+                self.last_status = Some(response);
+            }
+            EmbeddedResponse::Stats(_) => {
+                self.stats.status_updates += 1;
+                self.last_stats = Some(response);
+            }
+            _ => {}
+        }
+    }
+
+    /// Anchor device time to host time; uptime going backwards means a reboot
+    fn track_uptime(&mut self, uptime_seconds: u32, now: u64) {
+        if self.last_uptime.is_some_and(|previous| uptime_seconds < previous) {
+            self.seen_readings.clear();
+            self.stats.device_reboots += 1;
+        }
+
+        self.last_uptime = Some(uptime_seconds);
+        self.device_boot_time = Some(now.saturating_sub(uptime_seconds as u64));
+    }
+
+    /// Map a device timestamp to host Unix seconds
+    fn host_timestamp(&mut self, device_timestamp: u32, now: u64) -> u64 {
+        if device_timestamp >= UNIX_TIMESTAMP_THRESHOLD {
+            return device_timestamp as u64;
+        }
+
+        // Until a status arrives, assume the first reading was taken just now
+        let boot_time = *self
+            .device_boot_time
+            .get_or_insert(now.saturating_sub(device_timestamp as u64));
+        boot_time + device_timestamp as u64
+    }
+
+    /// Store a reading; `from_response` marks one sent in answer to a command
+    fn store_reading(&mut self, reading: EmbeddedTemperatureReading, now: u64, from_response: bool) {
+        // Simulation timestamps are whole seconds, so several samples can share one;
+        // only a command answer can repeat a reading the periodic output already sent
+        let key = (reading.timestamp, reading.temperature.celsius.to_bits());
+        if !self.seen_readings.insert(key) && from_response {
+            self.stats.duplicates += 1;
+            return;
+        }
+        if self.seen_readings.len() > self.capacity {
+            self.seen_readings.pop_first();
+        }
+
+        let timestamp = self.host_timestamp(reading.timestamp, now);
+        self.store.add_reading(TemperatureReading {
+            temperature: reading.temperature,
+            timestamp,
+        });
+        self.stats.readings += 1;
+
+        if self.echo {
+            println!("🌡️  {:.1}°C @ {}", reading.temperature.celsius, timestamp);
+        }
+    }
+}
+
+/// Where the gateway reads device output from
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    Stdin,
+    Path(String),
+}
+
+fn run(gateway: &mut Gateway, source: &Source, follow: bool) {
+    let result = match source {
+        Source::Stdin => gateway.ingest_reader(io::stdin().lock()),
+        Source::Path(path) if follow => {
+            follow_path(gateway, path);
+            Ok(())
+        }
+        Source::Path(path) => File::open(path).and_then(|file| gateway.ingest_reader(BufReader::new(file))),
+    };
+
+    if let Err(e) = result {
+        eprintln!("⚠️  Read error: {}", e);
+    }
+}
+
+/// Tail `path` forever, reopening it only if a device or pty fails
+fn follow_path(gateway: &mut Gateway, path: &str) {
+    let mut was_connected = false;
+
+    loop {
+        match File::open(path) {
+            Ok(file) => {
+                // Only a device that was lost and came back counts as a reconnect
+                if was_connected {
+                    gateway.stats.reconnects += 1;
+                }
+                was_connected = true;
+
+                let is_regular_file = file.metadata().map(|m| m.is_file()).unwrap_or(false);
+                let mut reader = BufReader::new(file);
+                let mut partial = PartialLine::new();
+
+                let error = loop {
+                    if let Err(e) = gateway.ingest_available(&mut reader, &mut partial) {
+                        break e;
+                    }
+                    sleep(POLL_INTERVAL);
+                };
+
+                eprintln!("⚠️  Read error on {}: {}", path, error);
+                if is_regular_file {
+                    // Reopening would replay the file from the start
+                    return;
+                }
+            }
+            Err(e) => eprintln!("⚠️  Cannot open {}: {}", path, e),
+        }
+
+        eprintln!("🔌 Retrying in {}s...", RECONNECT_DELAY.as_secs());
+        sleep(RECONNECT_DELAY);
+    }
+}
+
+fn main() {
+    let mut follow = false;
+    let mut source = Source::Stdin;
+
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--follow" => follow = true,
+            "-" => source = Source::Stdin,
+            path => source = Source::Path(path.to_string()),
+        }
+    }
+
+    if follow && source == Source::Stdin {
+        eprintln!("❌ --follow needs a PATH; stdin is read until it is closed");
+        std::process::exit(2);
+    }
+
+    println!("🌉 Temperature Gateway");
+    println!("======================");
+
+    let mut gateway = Gateway::new(STORE_CAPACITY);
+    gateway.set_echo(follow);
+    run(&mut gateway, &source, follow);
+
+    let stats = gateway.stats();
+    println!("\n📈 Gateway Summary:");
+    println!("  📥 Lines: {} ({} ignored)", stats.lines, stats.ignored_lines);
+    println!("  🌡️  Readings stored: {} ({} duplicates skipped)", stats.readings, stats.duplicates);
+    println!("  📊 Status updates: {}", stats.status_updates);
+    println!("  🔁 Device reboots: {}", stats.device_reboots);
+    println!("  🔌 Reconnects: {}", stats.reconnects);
+
+    if let Some(EmbeddedResponse::Status { uptime_seconds, reading_count, .. }) = gateway.last_status() {
+        println!("  ⏱️  Device uptime: {}s, {} readings on device", uptime_seconds, reading_count);
+    }
+
+    if let Some(EmbeddedResponse::Stats(device_stats)) = gateway.last_stats() {
+        println!("  📟 Device stats: {:.1}°C - {:.1}°C over {} readings",
+                 device_stats.min.celsius, device_stats.max.celsius, device_stats.count);
+    }
+
+    if let Some(store_stats) = gateway.store().calculate_stats() {
+        println!("  🌡️  Range: {:.1}°C - {:.1}°C, average {:.1}°C",
+                 store_stats.min.celsius, store_stats.max.celsius, store_stats.average.celsius);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn reading_line(celsius: f32, timestamp: u32) -> String {
+        format!("READING_JSON: {{\"temperature\":{{\"celsius\":{:.1}}},\"timestamp\":{}}}", celsius, timestamp)
+    }
+
+    fn status_line(uptime_seconds: u32) -> String {
+        format!("STATUS_JSON: {{\"Status\":{{\"uptime_seconds\":{},\"reading_count\":1,\"sample_rate\":10,\"buffer_usage\":1}}}}",
+                uptime_seconds)
+    }
+
+    fn response_line(celsius: f32, timestamp: u32) -> String {
+        format!("RESPONSE_JSON: {{\"Reading\":{{\"temperature\":{{\"celsius\":{:.1}}},\"timestamp\":{}}}}}", celsius, timestamp)
+    }
+
+    fn stored_timestamps(gateway: &Gateway) -> Vec<u64> {
+        gateway.store().get_all().iter().map(|reading| reading.timestamp).collect()
+    }
+
+    #[test]
+    fn test_parse_reading_line() {
+        let line = r#"READING_JSON: {"temperature":{"celsius":24.5},"timestamp":42}"#;
+
+        match parse_line(line) {
+            Some(DeviceLine::Reading(reading)) => {
+                assert_eq!(reading.temperature.celsius, 24.5);
+                assert_eq!(reading.timestamp, 42);
+            }
+            other => panic!("Expected reading, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_status_and_stats_lines() {
+        let status = r#"STATUS_JSON: {"Status":{"uptime_seconds":10,"reading_count":3,"sample_rate":10,"buffer_usage":4}}"#;
+        assert!(matches!(
+            parse_line(status),
+            Some(DeviceLine::Status(EmbeddedResponse::Status { reading_count: 3, .. }))
+        ));
+
+        let stats = r#"STATS_JSON: {"Stats":{"min":{"celsius":20.0},"max":{"celsius":30.0},"average":{"celsius":25.0},"count":3}}"#;
+        assert!(matches!(parse_line(stats), Some(DeviceLine::Stats(EmbeddedResponse::Stats(_)))));
+    }
+
+    #[test]
+    fn test_prefix_must_match_body() {
+        let reading = r#"{"Reading":{"temperature":{"celsius":24.5},"timestamp":42}}"#;
+        assert!(parse_line(&format!("STATUS_JSON: {}", reading)).is_none());
+        assert!(parse_line(&format!("STATS_JSON: {}", reading)).is_none());
+        assert!(matches!(
+            parse_line(&format!("RESPONSE_JSON: {}", reading)),
+            Some(DeviceLine::Response(EmbeddedResponse::Reading(_)))
+        ));
+
+        let mut gateway = Gateway::new(10);
+        gateway.ingest_line_at(&format!("STATUS_JSON: {}", reading), 1000);
+        assert_eq!(gateway.store().get_all().len(), 0);
+        assert_eq!(gateway.stats().ignored_lines, 1);
+    }
+
+    #[test]
+    fn test_garbage_lines_are_ignored() {
+        assert!(parse_line("").is_none());
+        assert!(parse_line("🌡️ ESP32-C3 Temperature Monitor Starting").is_none());
+        assert!(parse_line("READING_JSON: {\"temperature\":").is_none());
+        assert!(parse_line("STATUS_JSON: not json at all").is_none());
+    }
+
+    #[test]
+    fn test_prefix_after_noise_is_accepted() {
+        let line = "\u{fffd}\u{fffd}I (123) READING_JSON: {\"temperature\":{\"celsius\":21.0},\"timestamp\":1}\r";
+        assert!(matches!(parse_line(line), Some(DeviceLine::Reading(_))));
+    }
+
+    #[test]
+    fn test_gateway_ingests_mixed_stream() {
+        let output = concat!(
+            "🌡️ ESP32-C3 Temperature Monitor Starting\n",
+            "READING_JSON: {\"temperature\":{\"celsius\":20.0},\"timestamp\":1}\n",
+            "garbage \u{0}\u{1} line\n",
+            "READING_JSON: {\"temperature\":{\"celsius\":30.0},\"timestamp\":2}\n",
+            "STATUS_JSON: {\"Status\":{\"uptime_seconds\":2,\"reading_count\":2,\"sample_rate\":10,\"buffer_usage\":3}}\n",
+            "RESPONSE_JSON: {\"Reading\":{\"temperature\":{\"celsius\":30.0},\"timestamp\":2}}\n",
+            "READING_JSON: {\"temperature\":{\"celsius\":99.0},\"times",
+        );
+
+        let mut gateway = Gateway::new(10);
+        gateway.ingest_reader(output.as_bytes()).unwrap();
+
+        let stats = gateway.stats();
+        assert_eq!(stats.lines, 7);
+        assert_eq!(stats.readings, 2);
+        assert_eq!(stats.duplicates, 1);
+        assert_eq!(stats.status_updates, 1);
+        assert_eq!(stats.ignored_lines, 3);
+
+        assert_eq!(gateway.store().get_all().len(), 2);
+        let store_stats = gateway.store().calculate_stats().unwrap();
+        assert_eq!(store_stats.min.celsius, 20.0);
+        assert_eq!(store_stats.max.celsius, 30.0);
+        assert!(gateway.last_status().is_some());
+    }
+
+    #[test]
+    fn test_device_timestamps_map_to_host_time() {
+        let mut gateway = Gateway::new(10);
+
+        // Replayed log: everything arrives at once, spacing still comes from the device
+        gateway.ingest_line_at(&reading_line(20.0, 5), 5000);
+        gateway.ingest_line_at(&reading_line(21.0, 8), 5000);
+        assert_eq!(stored_timestamps(&gateway), vec![5000, 5003]);
+
+        // A status re-anchors device boot to host time
+        gateway.ingest_line_at(&status_line(10), 1000);
+        gateway.ingest_line_at(&reading_line(22.0, 12), 1002);
+        assert_eq!(stored_timestamps(&gateway)[2], 1002);
+
+        // Wall-clock timestamps from the simulation build are kept as they are
+        gateway.ingest_line_at(&reading_line(23.0, 1_700_000_000), 1003);
+        assert_eq!(stored_timestamps(&gateway)[3], 1_700_000_000);
+    }
+
+    #[test]
+    fn test_samples_sharing_a_second_are_all_stored() {
+        let mut gateway = Gateway::new(10);
+
+        gateway.ingest_line_at(&reading_line(20.0, 1_700_000_000), 1_700_000_000);
+        gateway.ingest_line_at(&reading_line(20.0, 1_700_000_000), 1_700_000_000);
+        gateway.ingest_line_at(&reading_line(20.5, 1_700_000_000), 1_700_000_000);
+
+        // A command answer for a reading taken in that second but not yet sent is new
+        gateway.ingest_line_at(&response_line(21.0, 1_700_000_000), 1_700_000_000);
+        // Repeating one that was already sent is not
+        gateway.ingest_line_at(&response_line(20.5, 1_700_000_000), 1_700_000_000);
+
+        assert_eq!(gateway.stats().readings, 4);
+        assert_eq!(gateway.stats().duplicates, 1);
+    }
+
+    #[test]
+    fn test_reboot_resets_duplicate_detection() {
+        let mut gateway = Gateway::new(10);
+
+        gateway.ingest_line_at(&status_line(100), 1000);
+        gateway.ingest_line_at(&reading_line(20.0, 100), 1000);
+        gateway.ingest_line_at(&response_line(20.0, 100), 1001);
+        assert_eq!(gateway.stats().duplicates, 1);
+
+        // Uptime went backwards: the device restarted and reuses timestamps
+        gateway.ingest_line_at(&status_line(1), 2000);
+        gateway.ingest_line_at(&response_line(20.0, 100), 2099);
+
+        assert_eq!(gateway.stats().device_reboots, 1);
+        assert_eq!(gateway.stats().readings, 2);
+        assert_eq!(stored_timestamps(&gateway), vec![1000, 2099]);
+    }
+
+    #[test]
+    fn test_tailing_a_growing_file_reads_each_line_once() {
+        let path = std::env::temp_dir().join(format!("temp_gateway_tail_{}.log", std::process::id()));
+        let mut writer = File::create(&path).unwrap();
+        write!(writer, "{}\n{}", reading_line(20.0, 1), &reading_line(21.0, 2)[..20]).unwrap();
+        writer.flush().unwrap();
+
+        let mut gateway = Gateway::new(10);
+        let mut reader = BufReader::new(File::open(&path).unwrap());
+        let mut partial = PartialLine::new();
+
+        gateway.ingest_available(&mut reader, &mut partial).unwrap();
+        assert_eq!(gateway.stats().readings, 1);
+        assert!(!partial.is_empty());
+
+        // The rest of the half-written line arrives later
+        writeln!(writer, "{}", &reading_line(21.0, 2)[20..]).unwrap();
+        writer.flush().unwrap();
+        gateway.ingest_available(&mut reader, &mut partial).unwrap();
+        gateway.ingest_available(&mut reader, &mut partial).unwrap();
+
+        assert_eq!(gateway.stats().readings, 2);
+        assert_eq!(gateway.stats().lines, 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_overlong_line_is_dropped_until_newline() {
+        let mut gateway = Gateway::new(10);
+        let mut partial = PartialLine::new();
+
+        // Noise without a newline, arriving in pieces, doesn't grow the buffer
+        let noise = vec![b'\xaa'; MAX_LINE_LENGTH];
+        for _ in 0..4 {
+            gateway.ingest_available(&mut &noise[..], &mut partial).unwrap();
+            assert!(partial.bytes.len() <= MAX_LINE_LENGTH);
+        }
+
+        let rest = format!("noise\n{}\n", reading_line(22.0, 5));
+        gateway.ingest_available(&mut rest.as_bytes(), &mut partial).unwrap();
+
+        assert_eq!(gateway.stats().lines, 2);
+        assert_eq!(gateway.stats().ignored_lines, 1);
+        assert_eq!(gateway.stats().readings, 1);
+    }
+
+    #[test]
+    fn test_invalid_utf8_does_not_stop_ingestion() {
+        let mut output = b"\xff\xfe\n".to_vec();
+        output.extend_from_slice(b"READING_JSON: {\"temperature\":{\"celsius\":22.0},\"timestamp\":5}\n");
+
+        let mut gateway = Gateway::new(10);
+        gateway.ingest_reader(&output[..]).unwrap();
+
+        assert_eq!(gateway.stats().ignored_lines, 1);
+        assert_eq!(gateway.store().get_all().len(), 1);
+    }
+}